@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, WebviewUrl, WebviewWindowBuilder};
+use tokio::sync::oneshot;
+
+/// How `connect_vpn` obtains credentials for a portal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+    #[default]
+    Password,
+    Saml,
+}
+
+// GlobalProtect's SAML redirect lands back on the portal with the auth
+// cookie tucked into one of these query params, depending on gateway vendor.
+const COOKIE_PARAMS: &[&str] = &["prelogin-cookie", "portal-userauthcookie", "auth-cookie"];
+
+/// Open the portal's SAML/OIDC login page in a dedicated webview window and
+/// wait for the final redirect to hand back a GlobalProtect auth cookie.
+///
+/// The cookie is returned to the caller so `connect_vpn` can feed it to
+/// `openconnect --cookie-on-stdin` instead of a username/password.
+#[tauri::command]
+pub async fn saml_login(app_handle: tauri::AppHandle, portal: String) -> Result<String, String> {
+    let login_url = format!("https://{}/global-protect/login.esp", portal);
+    let url = login_url
+        .parse()
+        .map_err(|e| format!("Invalid portal URL: {}", e))?;
+
+    // `on_navigation` is an `Fn`, not `FnMut`, and may fire more than once, so
+    // the oneshot sender has to be taken out of a `Mutex` to guarantee it's
+    // only ever consumed by the first matching navigation.
+    let (tx, rx) = oneshot::channel::<String>();
+    let tx = Mutex::new(Some(tx));
+
+    let window = WebviewWindowBuilder::new(&app_handle, "saml-login", WebviewUrl::External(url))
+        .title("GlobalProtect Login")
+        .inner_size(480.0, 640.0)
+        .on_navigation(move |nav_url| {
+            if let Some(cookie) = extract_cookie(nav_url.as_str()) {
+                if let Some(tx) = tx.lock().unwrap().take() {
+                    // Ignore send errors: the receiver may have already timed out.
+                    let _ = tx.send(cookie);
+                }
+                return false;
+            }
+            true
+        })
+        .build()
+        .map_err(|e| format!("Failed to open login window: {}", e))?;
+
+    // Await the cookie instead of blocking a worker thread on
+    // `recv_timeout`: this command runs on the async runtime, and a 5-minute
+    // blocking call here would pin that thread for the whole wait.
+    let cookie = match tokio::time::timeout(Duration::from_secs(300), rx).await {
+        Ok(Ok(cookie)) => Ok(cookie),
+        _ => Err("Timed out waiting for SAML login".to_string()),
+    };
+
+    let _ = window.close();
+    let _ = app_handle.emit("saml-login-finished", cookie.is_ok());
+
+    cookie
+}
+
+/// Pull a GlobalProtect auth cookie out of a navigated URL's query string or
+/// fragment.
+///
+/// Known limitation: some IdPs hand the cookie back in a response header or
+/// body (or set it via `document.cookie` after the page loads) rather than
+/// in the URL itself, in which case this never fires and `saml_login` times
+/// out. Catching those would mean intercepting the HTTP response — e.g. a
+/// custom protocol handler in front of the webview — rather than just the
+/// navigated URL; that's a bigger change than this function, so for now
+/// portals that only return the cookie that way aren't supported here.
+fn extract_cookie(url: &str) -> Option<String> {
+    // Split off the fragment first: a query string before a '#' must not
+    // have the fragment folded into its last value.
+    let (rest, fragment) = match url.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (url, None),
+    };
+    let query = rest.split_once('?').map(|(_, q)| q);
+
+    for params in [query, fragment].into_iter().flatten() {
+        // `form_urlencoded::parse` percent-decodes both sides, which matters
+        // here: a base64 cookie value containing `+`/`/`/`=` arrives
+        // percent-encoded in the redirect, and openconnect needs the
+        // decoded bytes, not the encoded string.
+        for (key, value) in url::form_urlencoded::parse(params.as_bytes()) {
+            if COOKIE_PARAMS.contains(&key.as_ref()) {
+                return Some(value.into_owned());
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,77 @@
+// Small companion CLI that speaks the GUI's control-socket protocol, so VPN
+// connect/disconnect/status can be driven from scripts, systemd units, or
+// network hooks without interacting with the Tauri UI. The GUI must be
+// running: it is what serves the control socket.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+
+fn socket_path() -> std::path::PathBuf {
+    // Mirrors `app_handle.path().app_data_dir()` on the GUI side (ipc.rs),
+    // which is derived from the same `globalprotect_lib::APP_ID`.
+    dirs::data_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join(globalprotect_lib::APP_ID)
+        .join("control.sock")
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: globalprotect-cli <connect|disconnect|status|read-logs> [--portal P --username U --password W]"
+    );
+    std::process::exit(2);
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_else(|| usage());
+
+    let request = match command.as_str() {
+        "status" => serde_json::json!({ "cmd": "status" }),
+        "disconnect" => serde_json::json!({ "cmd": "disconnect" }),
+        "read-logs" => serde_json::json!({ "cmd": "read-logs" }),
+        "connect" => {
+            let mut portal = None;
+            let mut username = None;
+            let mut password = None;
+            let rest: Vec<String> = args.collect();
+            let mut i = 0;
+            while i + 1 < rest.len() {
+                match rest[i].as_str() {
+                    "--portal" => portal = Some(rest[i + 1].clone()),
+                    "--username" => username = Some(rest[i + 1].clone()),
+                    "--password" => password = Some(rest[i + 1].clone()),
+                    _ => {}
+                }
+                i += 2;
+            }
+            let (Some(portal), Some(username)) = (portal, username) else {
+                usage();
+            };
+            serde_json::json!({
+                "cmd": "connect",
+                "config": {
+                    "portal": portal,
+                    "username": username,
+                    "password": password,
+                    "auth_mode": "password",
+                },
+            })
+        }
+        _ => usage(),
+    };
+
+    let path = socket_path();
+    let mut stream = UnixStream::connect(&path).unwrap_or_else(|e| {
+        eprintln!("Failed to connect to {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+
+    let line = serde_json::to_string(&request).expect("serialize request");
+    writeln!(stream, "{}", line).expect("write request");
+
+    let mut reader = BufReader::new(stream);
+    let mut response = String::new();
+    reader.read_line(&mut response).expect("read response");
+    print!("{}", response);
+}
@@ -0,0 +1,140 @@
+use std::net::ToSocketAddrs;
+use std::process::{Command, Stdio};
+
+const NFT_TABLE: &str = "globalprotect_killswitch";
+const IPTABLES_CHAIN: &str = "GP_KILLSWITCH";
+
+fn which(bin: &str) -> bool {
+    Command::new("which")
+        .arg(bin)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+fn resolve_portal_ip(portal: &str) -> Result<String, String> {
+    let addr = (portal, 443)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve portal address: {}", e))?
+        .next()
+        .ok_or_else(|| "Portal did not resolve to any address".to_string())?;
+    Ok(addr.ip().to_string())
+}
+
+/// Install a default-deny outbound firewall so that if openconnect dies
+/// unexpectedly, no traffic leaks out the physical interface: only
+/// loopback, tun* interfaces, the portal's resolved IP, and already-open
+/// connections stay reachable. Prefers nftables, falling back to iptables.
+///
+/// GlobalProtect portals routinely hand off the actual tunnel to a gateway
+/// host that's different from (and not known ahead of the handshake from)
+/// the portal hostname, so this must not be called until *after* that
+/// hand-off has completed — i.e. once the status monitor confirms the
+/// tunnel is up, not right after spawning openconnect. At that point the
+/// already-established portal/gateway sockets are covered by the
+/// established/related rule below regardless of which IP they ended up on;
+/// only the portal's own address needs an explicit allow, for things like
+/// re-auth that open a fresh connection back to it.
+pub fn enable(portal: &str) -> Result<(), String> {
+    let portal_ip = resolve_portal_ip(portal)?;
+
+    if which("nft") {
+        enable_nftables(&portal_ip)
+    } else if which("iptables") {
+        enable_iptables(&portal_ip)
+    } else {
+        Err("Neither nft nor iptables is available for the kill switch".to_string())
+    }
+}
+
+/// Tear down whatever kill switch rules are currently installed. Safe to
+/// call even when none were ever applied.
+pub fn disable() {
+    if which("nft") {
+        let _ = Command::new("sudo")
+            .args(["nft", "delete", "table", "inet", NFT_TABLE])
+            .status();
+    }
+    if which("iptables") {
+        let _ = Command::new("sudo")
+            .args(["iptables", "-D", "OUTPUT", "-j", IPTABLES_CHAIN])
+            .status();
+        let _ = Command::new("sudo")
+            .args(["iptables", "-F", IPTABLES_CHAIN])
+            .status();
+        let _ = Command::new("sudo")
+            .args(["iptables", "-X", IPTABLES_CHAIN])
+            .status();
+    }
+}
+
+fn enable_nftables(portal_ip: &str) -> Result<(), String> {
+    let script = format!(
+        "table inet {table} {{\n\
+           chain output {{\n\
+             type filter hook output priority 0; policy drop;\n\
+             oifname \"lo\" accept\n\
+             oifname \"tun*\" accept\n\
+             ct state established,related accept\n\
+             ip daddr {ip} accept\n\
+           }}\n\
+         }}\n",
+        table = NFT_TABLE,
+        ip = portal_ip,
+    );
+
+    let mut child = Command::new("sudo")
+        .arg("nft")
+        .arg("-f")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to invoke nft: {}", e))?;
+
+    {
+        use std::io::Write;
+        let stdin = child.stdin.as_mut().ok_or("Failed to open nft stdin")?;
+        stdin
+            .write_all(script.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err("nft rejected the kill switch ruleset".to_string());
+    }
+    Ok(())
+}
+
+fn enable_iptables(portal_ip: &str) -> Result<(), String> {
+    let rules: &[&[&str]] = &[
+        &["-N", IPTABLES_CHAIN],
+        &["-A", IPTABLES_CHAIN, "-o", "lo", "-j", "ACCEPT"],
+        &["-A", IPTABLES_CHAIN, "-o", "tun+", "-j", "ACCEPT"],
+        &[
+            "-A",
+            IPTABLES_CHAIN,
+            "-m",
+            "state",
+            "--state",
+            "ESTABLISHED,RELATED",
+            "-j",
+            "ACCEPT",
+        ],
+        &["-A", IPTABLES_CHAIN, "-d", portal_ip, "-j", "ACCEPT"],
+        &["-A", IPTABLES_CHAIN, "-j", "DROP"],
+        &["-A", "OUTPUT", "-j", IPTABLES_CHAIN],
+    ];
+
+    for rule in rules {
+        let status = Command::new("sudo")
+            .arg("iptables")
+            .args(*rule)
+            .status()
+            .map_err(|e| format!("Failed to invoke iptables: {}", e))?;
+        if !status.success() {
+            return Err("iptables rejected the kill switch ruleset".to_string());
+        }
+    }
+    Ok(())
+}
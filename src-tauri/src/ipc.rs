@@ -0,0 +1,120 @@
+use crate::{do_connect, do_disconnect, do_read_logs, do_status, VpnConfig, VpnState};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use tauri::{AppHandle, Manager};
+
+/// One line-delimited JSON command as sent by `globalprotect-cli`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+enum IpcCommand {
+    Connect { config: VpnConfig },
+    Disconnect,
+    Status,
+    ReadLogs,
+}
+
+#[derive(Debug, Serialize)]
+struct IpcResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl IpcResponse {
+    fn ok(data: Option<serde_json::Value>) -> Self {
+        Self {
+            ok: true,
+            data,
+            error: None,
+        }
+    }
+
+    fn err(error: String) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(error),
+        }
+    }
+}
+
+fn socket_path(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
+    Ok(app_dir.join("control.sock"))
+}
+
+/// Start the control socket the GUI listens on so that `globalprotect-cli`
+/// (or any script) can drive `connect`/`disconnect`/`status`/`read-logs`
+/// against this same running instance.
+///
+/// Fails fast if the socket already exists and answers, i.e. a GUI instance
+/// is already running.
+pub fn start_server(app_handle: AppHandle) -> Result<(), String> {
+    let path = socket_path(&app_handle)?;
+
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            return Err("Another instance is already running".to_string());
+        }
+        // Stale socket left behind by a crash; safe to replace.
+        let _ = std::fs::remove_file(&path);
+    }
+
+    let listener = UnixListener::bind(&path).map_err(|e| e.to_string())?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app_handle = app_handle.clone();
+            std::thread::spawn(move || {
+                tauri::async_runtime::block_on(handle_connection(app_handle, stream));
+            });
+        }
+    });
+
+    Ok(())
+}
+
+async fn handle_connection(app_handle: AppHandle, stream: UnixStream) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+    let mut writer = stream;
+
+    let mut line = String::new();
+    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+        return;
+    }
+
+    let state = app_handle.state::<VpnState>();
+    let response = match serde_json::from_str::<IpcCommand>(line.trim()) {
+        Ok(IpcCommand::Connect { config }) => match do_connect(&app_handle, &state, config).await {
+            Ok(()) => IpcResponse::ok(None),
+            Err(e) => IpcResponse::err(e),
+        },
+        Ok(IpcCommand::Disconnect) => match do_disconnect(&state).await {
+            Ok(()) => IpcResponse::ok(None),
+            Err(e) => IpcResponse::err(e),
+        },
+        Ok(IpcCommand::Status) => match do_status(&state).await {
+            Ok(connected) => IpcResponse::ok(Some(serde_json::json!({ "connected": connected }))),
+            Err(e) => IpcResponse::err(e),
+        },
+        Ok(IpcCommand::ReadLogs) => match do_read_logs(&app_handle).await {
+            Ok(logs) => IpcResponse::ok(Some(serde_json::json!({ "logs": logs }))),
+            Err(e) => IpcResponse::err(e),
+        },
+        Err(e) => IpcResponse::err(format!("Invalid command: {}", e)),
+    };
+
+    if let Ok(payload) = serde_json::to_string(&response) {
+        let _ = writeln!(writer, "{}", payload);
+    }
+}
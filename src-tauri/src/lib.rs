@@ -1,11 +1,33 @@
+mod auth;
+mod firewall;
+mod ipc;
+mod script;
+mod stats;
+mod status;
+mod vault;
+
+use auth::AuthMode;
 use serde::{Deserialize, Serialize};
+use status::SharedStatus;
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
-use tauri::{Manager, State};
+use tauri::{Listener, Manager, State};
+use vault::VaultKey;
+
+/// Tauri bundle identifier — must match `identifier` in tauri.conf.json.
+/// `app_handle.path().app_data_dir()` derives its path from this, so
+/// `globalprotect-cli` (which has no `AppHandle` of its own) reads this
+/// constant to build the matching socket path instead of hardcoding the
+/// identifier a second time and risking drift.
+pub const APP_ID: &str = "com.globalprotect.clone";
 
 #[derive(Default)]
 struct VpnState {
     child: Arc<Mutex<Option<Child>>>,
+    child_pid: Arc<Mutex<Option<u32>>>,
+    status: SharedStatus,
+    vault_secret: VaultKey,
+    connected_at: Arc<Mutex<Option<i64>>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,6 +37,26 @@ struct VpnConfig {
     password: Option<String>,
     notifications_enabled: Option<bool>,
     auto_connect: Option<bool>,
+    #[serde(default)]
+    auth_mode: AuthMode,
+    /// SAML auth cookie captured by `saml_login`, used in place of a password
+    /// when `auth_mode` is `Saml`.
+    saml_cookie: Option<String>,
+    /// Whether the secret for this config is sealed in `vault.json` rather
+    /// than stored in `password` here.
+    secret_encrypted: Option<bool>,
+    /// When true, installs a default-deny outbound firewall for the
+    /// lifetime of the tunnel so a crashed openconnect can't leak traffic.
+    enable_killswitch: Option<bool>,
+    /// Override for the vpnc-script passed to `--script`; when unset we
+    /// fall back to a known system install or a vendored copy.
+    vpnc_script_path: Option<String>,
+    /// Extra DNS servers to force via the vpnc-script, overriding whatever
+    /// the gateway pushes.
+    dns_servers: Option<Vec<String>>,
+    /// Split-tunnel "network/mask" routes to push through the vpnc-script;
+    /// when set, they replace the gateway's own split-tunnel list.
+    split_tunnel_routes: Option<Vec<String>>,
 }
 
 #[tauri::command]
@@ -32,6 +74,16 @@ async fn connect_vpn(
     app_handle: tauri::AppHandle,
     config: VpnConfig,
     state: State<'_, VpnState>,
+) -> Result<(), String> {
+    do_connect(&app_handle, &state, config).await
+}
+
+// Shared by the `connect_vpn` Tauri command and the control-socket server so
+// the GUI and `globalprotect-cli` drive the same openconnect child.
+pub(crate) async fn do_connect(
+    app_handle: &tauri::AppHandle,
+    state: &VpnState,
+    config: VpnConfig,
 ) -> Result<(), String> {
     let mut child_guard = state.child.lock().map_err(|_| "Failed to lock state")?;
 
@@ -40,15 +92,53 @@ async fn connect_vpn(
         let _ = existing.kill();
     }
 
+    // Resolve the secret up front so a locked vault fails fast instead of
+    // spawning openconnect first.
+    let secret: Option<String> = if config.auth_mode == AuthMode::Saml {
+        config.saml_cookie.clone()
+    } else if config.secret_encrypted.unwrap_or(false) {
+        let unlocked = state
+            .vault_secret
+            .lock()
+            .map_err(|_| "Failed to lock state")?
+            .clone();
+        Some(unlocked.ok_or("Vault is locked; call unlock_vault before connecting")?)
+    } else {
+        config.password.clone()
+    };
+
     // Prepare the command
     // Using sudo instead of pkexec for better scriptability and sudoers support
     let mut cmd = Command::new("sudo");
-    cmd.arg("openconnect")
-        .arg("--protocol=gp")
-        .arg("--passwd-on-stdin")
-        .arg(&config.portal)
-        .arg("--user")
-        .arg(&config.username);
+    cmd.arg("openconnect").arg("--protocol=gp");
+
+    match config.auth_mode {
+        AuthMode::Saml => {
+            cmd.arg("--cookie-on-stdin");
+        }
+        AuthMode::Password => {
+            cmd.arg("--passwd-on-stdin")
+                .arg("--user")
+                .arg(&config.username);
+        }
+    }
+    cmd.arg(&config.portal);
+
+    // `None` means no system script and auto-provisioning the vendored copy
+    // failed; fall back to openconnect's own built-in default rather than
+    // refusing to connect over what's just a missing DNS/route customization.
+    let script_path = match script::resolve(app_handle, config.vpnc_script_path.as_deref())? {
+        Some(path) => Some(script::wrap_with_overrides(
+            app_handle,
+            &path,
+            config.dns_servers.as_deref().unwrap_or(&[]),
+            config.split_tunnel_routes.as_deref().unwrap_or(&[]),
+        )?),
+        None => None,
+    };
+    if let Some(path) = &script_path {
+        cmd.arg("--script").arg(path);
+    }
 
     // Setup logging
     let app_dir = app_handle
@@ -57,18 +147,30 @@ async fn connect_vpn(
         .map_err(|e| e.to_string())?;
     let logs_dir = app_dir.join("logs");
     std::fs::create_dir_all(&logs_dir).map_err(|e| e.to_string())?;
-    
+
     let log_path = logs_dir.join("vpn.log");
     let log_file = std::fs::OpenOptions::new()
         .create(true)
         .append(true)
         .open(&log_path)
         .map_err(|e| format!("Failed to open log file: {}", e))?;
-    
+
     // Log start attempt
     use std::io::Write;
     if let Ok(mut file) = std::fs::OpenOptions::new().append(true).open(&log_path) {
-        let _ = writeln!(file, "\n--- Connection Attempt: {} ---", chrono::Local::now());
+        let _ = writeln!(
+            file,
+            "\n--- Connection Attempt: {} ---",
+            chrono::Local::now()
+        );
+        let _ = writeln!(
+            file,
+            "Using vpnc-script: {}",
+            script_path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "openconnect default".to_string())
+        );
     }
 
     let stderr_log = log_file.try_clone().map_err(|e| e.to_string())?;
@@ -81,14 +183,26 @@ async fn connect_vpn(
         .spawn()
         .map_err(|e| format!("Failed to start openconnect: {}", e))?;
 
-    // Send password to stdin
-    if let Some(password) = config.password {
+    // Send the password or SAML cookie to stdin, matching --passwd-on-stdin /
+    // --cookie-on-stdin above.
+    if let Some(secret) = secret {
         if let Some(mut stdin) = child.stdin.take() {
             use std::io::Write;
-            let _ = writeln!(stdin, "{}", password);
+            let _ = writeln!(stdin, "{}", secret);
         }
     }
 
+    // The kill switch itself is installed once the status monitor confirms
+    // the tunnel is actually up (see the `vpn-status-changed` listener in
+    // `run()`), not here — GlobalProtect portals commonly hand off to a
+    // gateway host discovered only during the handshake, and installing a
+    // default-deny policy before that hand-off completes blocks it.
+
+    *state.child_pid.lock().map_err(|_| "Failed to lock state")? = Some(child.id());
+    *state
+        .connected_at
+        .lock()
+        .map_err(|_| "Failed to lock state")? = Some(chrono::Local::now().timestamp());
     *child_guard = Some(child);
 
     Ok(())
@@ -96,6 +210,10 @@ async fn connect_vpn(
 
 #[tauri::command]
 async fn disconnect_vpn(state: State<'_, VpnState>) -> Result<(), String> {
+    do_disconnect(&state).await
+}
+
+pub(crate) async fn do_disconnect(state: &VpnState) -> Result<(), String> {
     // Use absolute path for pkill as defined in sudoers
     let _ = Command::new("sudo")
         .arg("-n")
@@ -119,37 +237,29 @@ async fn disconnect_vpn(state: State<'_, VpnState>) -> Result<(), String> {
     if let Some(mut child) = child_guard.take() {
         let _ = child.kill();
     }
+    *state.child_pid.lock().map_err(|_| "Failed to lock state")? = None;
+    *state
+        .connected_at
+        .lock()
+        .map_err(|_| "Failed to lock state")? = None;
+
+    firewall::disable();
 
     Ok(())
 }
 
 #[tauri::command]
-async fn get_vpn_status(_state: State<'_, VpnState>) -> Result<bool, String> {
-    // Check if openconnect process is running - use pgreg -f for better match
-    let pgrep = Command::new("pgrep")
-        .arg("-f")
-        .arg("openconnect")
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if !pgrep.status.success() {
-        return Ok(false);
-    }
-
-    // Also check if a tun interface exists
-    let ip_addr = Command::new("ip")
-        .arg("addr")
-        .arg("show")
-        .output()
-        .map_err(|e| e.to_string())?;
-    let output = String::from_utf8_lossy(&ip_addr.stdout);
+async fn get_vpn_status(state: State<'_, VpnState>) -> Result<bool, String> {
+    do_status(&state).await
+}
 
-    // Most VPNs use tun interfaces
-    Ok(output.contains("tun"))
+pub(crate) async fn do_status(state: &VpnState) -> Result<bool, String> {
+    let status = state.status.lock().map_err(|_| "Failed to lock state")?;
+    Ok(status.connected)
 }
 
 #[tauri::command]
-async fn save_config(app_handle: tauri::AppHandle, config: VpnConfig) -> Result<(), String> {
+async fn save_config(app_handle: tauri::AppHandle, mut config: VpnConfig) -> Result<(), String> {
     let app_dir = app_handle
         .path()
         .app_data_dir()
@@ -157,6 +267,15 @@ async fn save_config(app_handle: tauri::AppHandle, config: VpnConfig) -> Result<
     // Create directory if it doesn't exist
     std::fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
 
+    // Never persist a secret in plaintext once a vault has been set up:
+    // the real value lives sealed in vault.json / unlocked in memory, and
+    // this config only remembers that it's sealed.
+    if vault::exists(&app_handle) {
+        config.password = None;
+        config.saml_cookie = None;
+        config.secret_encrypted = Some(true);
+    }
+
     let path = app_dir.join("vpn_config.json");
     let content = serde_json::to_string(&config).map_err(|e| e.to_string())?;
     std::fs::write(path, content).map_err(|e| e.to_string())?;
@@ -195,6 +314,10 @@ async fn check_permissions() -> Result<bool, String> {
 
 #[tauri::command]
 async fn read_logs(app_handle: tauri::AppHandle) -> Result<String, String> {
+    do_read_logs(&app_handle).await
+}
+
+pub(crate) async fn do_read_logs(app_handle: &tauri::AppHandle) -> Result<String, String> {
     let app_dir = app_handle
         .path()
         .app_data_dir()
@@ -233,6 +356,14 @@ pub fn run() {
         .manage(VpnState::default())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
+            // Fail fast instead of running two GUI instances against the
+            // same openconnect child.
+            if let Err(e) = ipc::start_server(app.handle().clone()) {
+                eprintln!("Failed to start control socket: {}", e);
+                app.handle().exit(1);
+                return Ok(());
+            }
+
             let status_i =
                 MenuItem::with_id(app, "status", "Status: Disconnected", false, None::<&str>)?;
             let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
@@ -267,6 +398,7 @@ pub fn run() {
                             .arg("-f")
                             .arg("openconnect")
                             .status();
+                        firewall::disable();
                         app.exit(0);
                     }
                 })
@@ -285,83 +417,111 @@ pub fn run() {
                 })
                 .build(app)?;
 
-            // Background thread to update status periodically
+            // Replace the old fixed-interval pgrep/ip-addr poll with a
+            // netlink-driven monitor keyed to our tracked child PID.
+            let vpn_state = app.state::<VpnState>();
+            status::spawn_monitor(
+                app.handle().clone(),
+                vpn_state.child_pid.clone(),
+                vpn_state.status.clone(),
+            );
+            stats::spawn_monitor(
+                app.handle().clone(),
+                vpn_state.status.clone(),
+                vpn_state.connected_at.clone(),
+            );
+
+            // React to vpn-status-changed instead of polling ourselves.
             let app_handle = app.handle().clone();
-            std::thread::spawn(move || {
-                let mut last_connected = false;
-                loop {
-                    let connected = {
-                        let pgrep = Command::new("pgrep").arg("-f").arg("openconnect").output();
-                        let ip_addr = Command::new("ip").arg("addr").arg("show").output();
-
-                        let is_running = pgrep.map(|o| o.status.success()).unwrap_or(false);
-                        let has_tun = ip_addr
-                            .map(|o| String::from_utf8_lossy(&o.stdout).contains("tun"))
-                            .unwrap_or(false);
-
-                        is_running && has_tun
-                    };
-
-                    let text = if connected {
-                        "Status: Connected ✅"
+            app.listen_any("vpn-status-changed", move |event| {
+                let Ok(status) = serde_json::from_str::<status::VpnStatus>(event.payload()) else {
+                    return;
+                };
+
+                let text = if status.connected {
+                    "Status: Connected ✅"
+                } else {
+                    "Status: Disconnected ❌"
+                };
+                let _ = status_i.set_text(text);
+
+                let config_res = {
+                    let app_dir = app_handle.path().app_data_dir().unwrap_or_default();
+                    let path = app_dir.join("vpn_config.json");
+                    if path.exists() {
+                        std::fs::read_to_string(path)
+                            .ok()
+                            .and_then(|c| serde_json::from_str::<VpnConfig>(&c).ok())
                     } else {
-                        "Status: Disconnected ❌"
-                    };
-
-                    let _ = status_i.set_text(text);
-
-                    // Update tray icon only if status changed
-                    if connected != last_connected {
-                        // Send system notification if enabled
-                        let config_res = {
+                        None
+                    }
+                };
+
+                // Install the kill switch only once the tunnel is confirmed up: by
+                // this point the portal/gateway hand-off has already happened, so
+                // the established/related rule in `firewall::enable` covers it
+                // regardless of which IP the gateway turned out to be.
+                if status.connected
+                    && config_res
+                        .as_ref()
+                        .and_then(|c| c.enable_killswitch)
+                        .unwrap_or(false)
+                {
+                    if let Some(portal) = config_res.as_ref().map(|c| c.portal.as_str()) {
+                        if let Err(e) = firewall::enable(portal) {
                             let app_dir = app_handle.path().app_data_dir().unwrap_or_default();
-                            let path = app_dir.join("vpn_config.json");
-                            if path.exists() {
-                                std::fs::read_to_string(path).ok().and_then(|c| {
-                                    serde_json::from_str::<VpnConfig>(&c).ok()
-                                })
-                            } else {
-                                None
+                            let log_path = app_dir.join("logs").join("vpn.log");
+                            if let Ok(mut file) =
+                                std::fs::OpenOptions::new().append(true).open(&log_path)
+                            {
+                                use std::io::Write;
+                                let _ = writeln!(file, "Failed to enable kill switch: {}", e);
                             }
-                        };
-
-                        let notifications_enabled = config_res.as_ref().and_then(|c| c.notifications_enabled).unwrap_or(true);
-                        
-                        if notifications_enabled {
-                            use tauri_plugin_notification::NotificationExt;
-                            let title = if connected { "VPN Connected" } else { "VPN Disconnected" };
-                            let body = if connected { 
-                                format!("Successfully connected to {}", config_res.as_ref().map(|c| &c.portal).unwrap_or(&"portal".to_string()))
-                            } else { 
-                                "The VPN connection has been closed.".to_string()
-                            };
-                            
-                            let _ = app_handle.notification()
-                                .builder()
-                                .title(title)
-                                .body(body)
-                                .show();
                         }
+                    }
+                }
 
-                        if let Some(tray) = app_handle.tray_by_id("main-tray") {
-                            if connected {
-                                // Try to load the green icon
-                                if let Ok(img) =
-                                    tauri::image::Image::from_path("icons/connected.png")
-                                {
-                                    let _ = tray.set_icon(Some(img));
-                                }
-                            } else {
-                                // Back to default icon
-                                let _ = tray.set_icon(Some(
-                                    app_handle.default_window_icon().unwrap().clone(),
-                                ));
-                            }
+                let notifications_enabled = config_res
+                    .as_ref()
+                    .and_then(|c| c.notifications_enabled)
+                    .unwrap_or(true);
+
+                if notifications_enabled {
+                    use tauri_plugin_notification::NotificationExt;
+                    let title = if status.connected {
+                        "VPN Connected"
+                    } else {
+                        "VPN Disconnected"
+                    };
+                    let body = if status.connected {
+                        format!(
+                            "Successfully connected to {}",
+                            config_res
+                                .as_ref()
+                                .map(|c| &c.portal)
+                                .unwrap_or(&"portal".to_string())
+                        )
+                    } else {
+                        "The VPN connection has been closed.".to_string()
+                    };
+
+                    let _ = app_handle
+                        .notification()
+                        .builder()
+                        .title(title)
+                        .body(body)
+                        .show();
+                }
+
+                if let Some(tray) = app_handle.tray_by_id("main-tray") {
+                    if status.connected {
+                        if let Ok(img) = tauri::image::Image::from_path("icons/connected.png") {
+                            let _ = tray.set_icon(Some(img));
                         }
-                        last_connected = connected;
+                    } else {
+                        let _ =
+                            tray.set_icon(Some(app_handle.default_window_icon().unwrap().clone()));
                     }
-
-                    std::thread::sleep(std::time::Duration::from_secs(2));
                 }
             });
 
@@ -391,7 +551,10 @@ pub fn run() {
             load_config,
             check_permissions,
             read_logs,
-            clear_logs
+            clear_logs,
+            auth::saml_login,
+            vault::set_master_passphrase,
+            vault::unlock_vault
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -411,6 +574,7 @@ pub fn run() {
                     .arg("-f")
                     .arg("openconnect")
                     .status();
+                firewall::disable();
             }
             _ => {}
         });
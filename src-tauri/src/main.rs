@@ -13,9 +13,9 @@ fn main() {
     // 3. Last resort: Software rendering for OpenGL
     // Addresses `EGL_BAD_PARAMETER`
     std::env::set_var("LIBGL_ALWAYS_SOFTWARE", "1");
-    
+
     // Explicitly set the program name for Wayland/DE grouping
-    glib::set_prgname(Some("com.globalprotect.clone"));
+    glib::set_prgname(Some(globalprotect_lib::APP_ID));
     glib::set_application_name("GlobalProtect");
 
     globalprotect_lib::run()
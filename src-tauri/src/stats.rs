@@ -0,0 +1,114 @@
+use crate::status::SharedStatus;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Instantaneous throughput and cumulative totals for the tunnel the status
+/// monitor last detected, plus session uptime.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TrafficStats {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_rate_bytes_per_sec: f64,
+    pub tx_rate_bytes_per_sec: f64,
+    pub uptime_secs: i64,
+}
+
+fn read_counter(interface: &str, counter: &str) -> Option<u64> {
+    std::fs::read_to_string(format!(
+        "/sys/class/net/{}/statistics/{}",
+        interface, counter
+    ))
+    .ok()?
+    .trim()
+    .parse()
+    .ok()
+}
+
+/// Background task: every second, read rx/tx byte counters for whatever tun
+/// interface the netlink status monitor most recently detected, derive
+/// throughput from the delta, and push a `vpn-stats-changed` event plus an
+/// enriched tray tooltip.
+pub fn spawn_monitor(
+    app_handle: AppHandle,
+    status: SharedStatus,
+    connected_at: Arc<Mutex<Option<i64>>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut last: Option<(u64, u64)> = None;
+        loop {
+            let interface = status.lock().unwrap().interface.clone();
+
+            let stats = match interface {
+                Some(interface) => {
+                    let rx = read_counter(&interface, "rx_bytes").unwrap_or(0);
+                    let tx = read_counter(&interface, "tx_bytes").unwrap_or(0);
+
+                    let (rx_rate_bytes_per_sec, tx_rate_bytes_per_sec) = match last {
+                        Some((prev_rx, prev_tx)) => (
+                            rx.saturating_sub(prev_rx) as f64,
+                            tx.saturating_sub(prev_tx) as f64,
+                        ),
+                        None => (0.0, 0.0),
+                    };
+                    last = Some((rx, tx));
+
+                    let uptime_secs = connected_at
+                        .lock()
+                        .unwrap()
+                        .map(|since| (chrono::Local::now().timestamp() - since).max(0))
+                        .unwrap_or(0);
+
+                    TrafficStats {
+                        rx_bytes: rx,
+                        tx_bytes: tx,
+                        rx_rate_bytes_per_sec,
+                        tx_rate_bytes_per_sec,
+                        uptime_secs,
+                    }
+                }
+                None => {
+                    last = None;
+                    TrafficStats::default()
+                }
+            };
+
+            let _ = app_handle.emit("vpn-stats-changed", &stats);
+
+            if let Some(tray) = app_handle.tray_by_id("main-tray") {
+                let tooltip = if stats.uptime_secs > 0 {
+                    format!(
+                        "Connected — ↓{} ↑{}, {} uptime",
+                        format_rate(stats.rx_rate_bytes_per_sec),
+                        format_rate(stats.tx_rate_bytes_per_sec),
+                        format_uptime(stats.uptime_secs),
+                    )
+                } else {
+                    "GlobalProtect".to_string()
+                };
+                let _ = tray.set_tooltip(Some(tooltip));
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+}
+
+fn format_rate(bytes_per_sec: f64) -> String {
+    if bytes_per_sec >= 1024.0 * 1024.0 {
+        format!("{:.1} MB/s", bytes_per_sec / (1024.0 * 1024.0))
+    } else {
+        format!("{:.0} KB/s", bytes_per_sec / 1024.0)
+    }
+}
+
+fn format_uptime(total_secs: i64) -> String {
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    if hours > 0 {
+        format!("{}h{}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
@@ -0,0 +1,169 @@
+use futures::stream::TryStreamExt;
+use rtnetlink::new_connection;
+use rtnetlink::packet_route::address::AddressAttribute;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Snapshot of the tunnel we own, as seen from the kernel over rtnetlink.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VpnStatus {
+    pub connected: bool,
+    pub interface: Option<String>,
+    pub ip: Option<String>,
+}
+
+pub type SharedStatus = Arc<Mutex<VpnStatus>>;
+
+fn process_comm(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // Format is "pid (comm) state ppid ...". The comm itself may contain
+    // spaces/parens, so skip past the last ')' rather than splitting naively.
+    let after_comm = stat.rfind(')')?;
+    stat[after_comm + 1..]
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+fn children_of(pid: u32) -> Vec<u32> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|&candidate| parent_pid(candidate) == Some(pid))
+        .collect()
+}
+
+/// `state.child` is the `sudo` wrapper we spawned, not openconnect itself —
+/// sudo may exec in place or fork depending on `use_pty`, so walk its
+/// process subtree for the real `openconnect` PID instead of assuming
+/// `sudo_pid` owns the tun fd directly.
+fn resolve_openconnect_pid(sudo_pid: u32) -> Option<u32> {
+    let mut frontier = vec![sudo_pid];
+    let mut visited = std::collections::HashSet::new();
+    while let Some(pid) = frontier.pop() {
+        if !visited.insert(pid) {
+            continue;
+        }
+        if process_comm(pid).as_deref() == Some("openconnect") {
+            return Some(pid);
+        }
+        frontier.extend(children_of(pid));
+    }
+    None
+}
+
+/// Find the specific tun/tap interface that process `pid` holds open, by
+/// reading the kernel-reported `iff:` name out of `/proc/<pid>/fdinfo/<fd>`
+/// for whichever fd points at `/dev/net/tun`. A bare `readlink` of the fd
+/// only ever shows the shared clone device, not which interface was bound
+/// to it via `TUNSETIFF` — so without this we'd have no way to tell "our"
+/// tunnel apart from any other tun device that happens to exist on the host.
+fn tun_interface_name(pid: u32) -> Option<String> {
+    let fd_dir = format!("/proc/{}/fd", pid);
+    let entries = std::fs::read_dir(&fd_dir).ok()?;
+    for entry in entries.flatten() {
+        let Ok(target) = std::fs::read_link(entry.path()) else {
+            continue;
+        };
+        if !target.to_string_lossy().contains("tun") {
+            continue;
+        }
+
+        let fdinfo_path = format!(
+            "/proc/{}/fdinfo/{}",
+            pid,
+            entry.file_name().to_string_lossy()
+        );
+        let Ok(fdinfo) = std::fs::read_to_string(&fdinfo_path) else {
+            continue;
+        };
+        for line in fdinfo.lines() {
+            if let Some(name) = line.strip_prefix("iff:") {
+                return Some(name.trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Ask the kernel over rtnetlink for the tun/tunN interface belonging to
+/// our tracked openconnect child, plus its assigned IP address.
+pub async fn detect_tunnel(sudo_pid: u32) -> std::io::Result<VpnStatus> {
+    let Some(pid) = resolve_openconnect_pid(sudo_pid) else {
+        return Ok(VpnStatus::default());
+    };
+    let Some(name) = tun_interface_name(pid) else {
+        return Ok(VpnStatus::default());
+    };
+
+    let (connection, handle, _) = new_connection()?;
+    tauri::async_runtime::spawn(connection);
+
+    let mut links = handle.link().get().match_name(name.clone()).execute();
+    let Some(link) = links.try_next().await? else {
+        return Ok(VpnStatus::default());
+    };
+
+    let mut addrs = handle
+        .address()
+        .get()
+        .set_link_index_filter(link.header.index)
+        .execute();
+    let mut ip = None;
+    while let Some(addr) = addrs.try_next().await? {
+        if let Some(AddressAttribute::Address(a)) = addr
+            .attributes
+            .iter()
+            .find(|a| matches!(a, AddressAttribute::Address(_)))
+        {
+            ip = Some(a.to_string());
+            break;
+        }
+    }
+
+    Ok(VpnStatus {
+        connected: true,
+        interface: Some(name),
+        ip,
+    })
+}
+
+/// Background task that replaces the old fixed-interval `pgrep` / `ip addr
+/// show` poll: watches `child_pid` for our tunnel's up/down state and emits
+/// `vpn-status-changed` only when it actually changes.
+pub fn spawn_monitor(
+    app_handle: AppHandle,
+    child_pid: Arc<Mutex<Option<u32>>>,
+    shared: SharedStatus,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut last = VpnStatus::default();
+        loop {
+            let pid = *child_pid.lock().unwrap();
+            let status = match pid {
+                Some(pid) => detect_tunnel(pid).await.unwrap_or_default(),
+                None => VpnStatus::default(),
+            };
+
+            if status != last {
+                let _ = app_handle.emit("vpn-status-changed", &status);
+            }
+            *shared.lock().unwrap() = status.clone();
+            last = status;
+
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    });
+}
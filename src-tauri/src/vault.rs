@@ -0,0 +1,124 @@
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+/// Decrypted password held in memory only for the lifetime of the unlocked
+/// session; never written to disk.
+pub type VaultKey = Arc<Mutex<Option<String>>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedSecret {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn vault_path(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
+    Ok(app_dir.join("vault.json"))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn seal(passphrase: &str, plaintext: &str) -> Result<SealedSecret, String> {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+
+    let mut nonce_bytes = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let cipher = ChaCha20Poly1305::new(key.as_slice().into());
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), plaintext.as_bytes())
+        .map_err(|e| format!("Failed to seal secret: {}", e))?;
+
+    Ok(SealedSecret {
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce_bytes),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn open_sealed(passphrase: &str, sealed: &SealedSecret) -> Result<String, String> {
+    let salt = BASE64.decode(&sealed.salt).map_err(|e| e.to_string())?;
+    let nonce_bytes = BASE64.decode(&sealed.nonce).map_err(|e| e.to_string())?;
+    let ciphertext = BASE64
+        .decode(&sealed.ciphertext)
+        .map_err(|e| e.to_string())?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = ChaCha20Poly1305::new(key.as_slice().into());
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_slice())
+        .map_err(|_| "Incorrect passphrase or corrupted vault".to_string())?;
+
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+/// Derive a fresh key from `passphrase`, seal `secret` with it, and persist
+/// the result to `vault.json`. Also unlocks the vault in memory so the
+/// caller doesn't immediately have to call `unlock_vault` again.
+#[tauri::command]
+pub async fn set_master_passphrase(
+    app_handle: AppHandle,
+    passphrase: String,
+    secret: String,
+    state: State<'_, crate::VpnState>,
+) -> Result<(), String> {
+    let sealed = seal(&passphrase, &secret)?;
+    let path = vault_path(&app_handle)?;
+    let content = serde_json::to_string(&sealed).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())?;
+
+    *state
+        .vault_secret
+        .lock()
+        .map_err(|_| "Failed to lock state")? = Some(secret);
+    Ok(())
+}
+
+/// Re-derive the key from `passphrase` and, on success, hold the decrypted
+/// secret in memory so `connect_vpn` can use it.
+#[tauri::command]
+pub async fn unlock_vault(
+    app_handle: AppHandle,
+    passphrase: String,
+    state: State<'_, crate::VpnState>,
+) -> Result<(), String> {
+    let path = vault_path(&app_handle)?;
+    if !path.exists() {
+        return Err("No vault has been set up yet".to_string());
+    }
+
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let sealed: SealedSecret = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let secret = open_sealed(&passphrase, &sealed)?;
+
+    *state
+        .vault_secret
+        .lock()
+        .map_err(|_| "Failed to lock state")? = Some(secret);
+    Ok(())
+}
+
+/// Whether a vault has been configured on disk, regardless of whether it's
+/// currently unlocked in memory.
+pub fn exists(app_handle: &AppHandle) -> bool {
+    vault_path(app_handle).map(|p| p.exists()).unwrap_or(false)
+}